@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+
+/// Configures a flow step that delegates to an LLM assistant instead of a canned reply.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssistantStepMetadata {
+    pub assistant_id: String,
+    pub instructions: Option<String>,
+    pub tools: Vec<AssistantTool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum AssistantTool {
+    #[serde(rename = "code_interpreter")]
+    CodeInterpreter,
+    #[serde(rename = "function")]
+    Function {
+        name: String,
+        description: Option<String>,
+    },
+}
+
+/// A conversation with an assistant, keyed by `session_id` so it accumulates across steps.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Thread {
+    pub id: String,
+    pub session_id: String,
+    pub messages: Vec<Message>,
+}
+
+impl Thread {
+    pub fn new(id: impl Into<String>, session_id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            session_id: session_id.into(),
+            messages: Vec::new(),
+        }
+    }
+
+    pub fn push_user_message(&mut self, content: impl Into<String>) {
+        self.messages.push(Message {
+            role: MessageRole::User,
+            content: content.into(),
+        });
+    }
+
+    pub fn push_assistant_message(&mut self, content: impl Into<String>) {
+        self.messages.push(Message {
+            role: MessageRole::Assistant,
+            content: content.into(),
+        });
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Message {
+    pub role: MessageRole,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageRole {
+    User,
+    Assistant,
+}
+
+/// A single assistant invocation over a `Thread`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Run {
+    pub id: String,
+    pub thread_id: String,
+    pub status: RunStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required_action: Option<RequiredAction>,
+}
+
+impl Run {
+    pub fn queued(id: impl Into<String>, thread_id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            thread_id: thread_id.into(),
+            status: RunStatus::Queued,
+            required_action: None,
+        }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(self.status, RunStatus::Completed | RunStatus::Failed)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Queued,
+    InProgress,
+    RequiresAction,
+    Completed,
+    Failed,
+}
+
+/// Surfaced while a `Run` is `RequiresAction`, so the flow engine can resolve the
+/// requested tool calls before the run is advanced further.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RequiredAction {
+    pub tool_calls: Vec<ToolCall>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: String,
+}
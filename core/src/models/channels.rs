@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// A transport a bot can be reached on.
+///
+/// Wire form matches the `platform` strings already on the Gateway queue
+/// (`"whatsapp"`, `"telegram"`, ...), not the `SCREAMING_SNAKE_CASE` tags used
+/// by [`ChannelProperties`] for stored channel configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    WhatsApp,
+    Telegram,
+    WebChat,
+    Voice,
+}
+
+/// Per-channel configuration for a bot, internally tagged so the Gateway can
+/// round-trip whatever config it stored without knowing the concrete type up
+/// front (mirrors the Azure Bot Service `*ChannelProperties` pattern).
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ChannelProperties {
+    #[serde(rename = "WHATS_APP")]
+    WhatsApp(WhatsAppChannelProperties),
+    #[serde(rename = "TELEGRAM")]
+    Telegram(TelegramChannelProperties),
+    #[serde(rename = "WEB_CHAT")]
+    WebChat(WebChatChannelProperties),
+    #[serde(rename = "VOICE")]
+    Voice(VoiceChannelProperties),
+}
+
+impl ChannelProperties {
+    pub fn channel(&self) -> Channel {
+        match self {
+            ChannelProperties::WhatsApp(_) => Channel::WhatsApp,
+            ChannelProperties::Telegram(_) => Channel::Telegram,
+            ChannelProperties::WebChat(_) => Channel::WebChat,
+            ChannelProperties::Voice(_) => Channel::Voice,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            ChannelProperties::WhatsApp(p) => p.is_enabled,
+            ChannelProperties::Telegram(p) => p.is_enabled,
+            ChannelProperties::WebChat(p) => p.is_enabled,
+            ChannelProperties::Voice(p) => p.is_enabled,
+        }
+    }
+
+    /// What this channel can render in an `OutgoingPayload`.
+    pub fn capabilities(&self) -> ChannelCapabilities {
+        match self {
+            ChannelProperties::WhatsApp(_) => ChannelCapabilities {
+                text: true,
+                media_type_prefixes: &["image/", "audio/", "video/", "application/"],
+                ptt: true,
+            },
+            ChannelProperties::Telegram(_) => ChannelCapabilities {
+                text: true,
+                media_type_prefixes: &["image/", "audio/", "video/", "application/"],
+                ptt: false,
+            },
+            ChannelProperties::WebChat(_) => ChannelCapabilities {
+                text: true,
+                media_type_prefixes: &["image/"],
+                ptt: false,
+            },
+            ChannelProperties::Voice(_) => ChannelCapabilities {
+                text: false,
+                media_type_prefixes: &["audio/"],
+                ptt: false,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WhatsAppChannelProperties {
+    pub is_enabled: bool,
+    pub phone_number_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TelegramChannelProperties {
+    pub is_enabled: bool,
+    pub bot_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebChatChannelProperties {
+    pub is_enabled: bool,
+    #[serde(rename = "webhookUrlFragment")]
+    pub webhook_url_fragment: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VoiceChannelProperties {
+    pub is_enabled: bool,
+    #[serde(rename = "voiceSkillId")]
+    pub voice_skill_id: String,
+}
+
+/// Declares which parts of an `OutgoingPayload` a channel is able to render.
+#[derive(Debug, Clone, Copy)]
+pub struct ChannelCapabilities {
+    pub text: bool,
+    /// MIME-type prefixes (e.g. `"image/"`) this channel can attach.
+    pub media_type_prefixes: &'static [&'static str],
+    pub ptt: bool,
+}
+
+impl ChannelCapabilities {
+    pub fn allows_media_type(&self, media_type: &str) -> bool {
+        self.media_type_prefixes
+            .iter()
+            .any(|prefix| media_type.starts_with(prefix))
+    }
+}
+
+/// Maps a `bot_id` to the channels it has configured.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ChannelRegistry {
+    bots: HashMap<String, HashMap<Channel, ChannelProperties>>,
+}
+
+impl ChannelRegistry {
+    pub fn register(&mut self, bot_id: impl Into<String>, properties: ChannelProperties) {
+        self.bots
+            .entry(bot_id.into())
+            .or_default()
+            .insert(properties.channel(), properties);
+    }
+
+    /// All enabled channels configured for a bot, regardless of channel kind.
+    pub fn enabled_channels(&self, bot_id: &str) -> Vec<&ChannelProperties> {
+        self.bots
+            .get(bot_id)
+            .into_iter()
+            .flat_map(|channels| channels.values())
+            .filter(|properties| properties.is_enabled())
+            .collect()
+    }
+
+    pub fn properties(&self, bot_id: &str, channel: Channel) -> Option<&ChannelProperties> {
+        self.bots.get(bot_id)?.get(&channel)
+    }
+}
@@ -1,4 +1,9 @@
-use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::channels::{Channel, ChannelCapabilities};
+use super::payloads::IncomingMessage;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConditionalTimeMetadata {
@@ -6,6 +11,29 @@ pub struct ConditionalTimeMetadata {
     pub fallback: Option<BranchContent>,
 }
 
+impl From<ConditionalTimeMetadata> for ConditionalMetadata {
+    fn from(legacy: ConditionalTimeMetadata) -> Self {
+        ConditionalMetadata {
+            branches: legacy
+                .branches
+                .into_iter()
+                .map(|branch| ConditionalBranch {
+                    when: Condition::TimeBetween {
+                        start: branch.start_time,
+                        end: branch.end_time,
+                    },
+                    content: BranchContent {
+                        r#type: branch.r#type,
+                        content: branch.content,
+                        media_url: branch.media_url,
+                    },
+                })
+                .collect(),
+            fallback: legacy.fallback.unwrap_or_default(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TimeBranch {
     #[serde(rename = "startTime")]
@@ -20,14 +48,167 @@ pub struct TimeBranch {
     pub media_url: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct BranchContent {
+    #[serde(default)]
     pub r#type: String,
     pub content: Option<String>,
     #[serde(rename = "mediaUrl")]
     pub media_url: Option<String>,
 }
 
+/// A single branch of a [`ConditionalMetadata`]: content to send `when` a condition holds.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConditionalBranch {
+    pub when: Condition,
+    #[serde(flatten)]
+    pub content: BranchContent,
+}
+
+/// Content-aware replacement for [`ConditionalTimeMetadata`]: routes on message content,
+/// sender identity, whether the message was sent by the bot itself, channel, or time
+/// window instead of wall-clock time alone.
+///
+/// Branches are evaluated in declared order; the first whose `when` condition holds wins,
+/// falling back to `fallback` if none do.
+#[derive(Debug, Serialize)]
+pub struct ConditionalMetadata {
+    pub branches: Vec<ConditionalBranch>,
+    pub fallback: BranchContent,
+}
+
+impl<'de> Deserialize<'de> for ConditionalMetadata {
+    /// Accepts both the current `{branches: [{when, ...}], fallback}` shape and a stored
+    /// legacy `ConditionalTimeMetadata` document, so existing time-branch flows keep working
+    /// without a separate migration step.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Wire {
+            Current {
+                branches: Vec<ConditionalBranch>,
+                fallback: BranchContent,
+            },
+            Legacy(ConditionalTimeMetadata),
+        }
+
+        match Wire::deserialize(deserializer)? {
+            Wire::Current { branches, fallback } => Ok(ConditionalMetadata { branches, fallback }),
+            Wire::Legacy(legacy) => Ok(legacy.into()),
+        }
+    }
+}
+
+impl ConditionalMetadata {
+    pub fn evaluate(&self, ctx: &MessageContext, now: &str) -> &BranchContent {
+        self.branches
+            .iter()
+            .find(|branch| branch.when.evaluate(ctx, now))
+            .map(|branch| &branch.content)
+            .unwrap_or(&self.fallback)
+    }
+}
+
+/// Minimal, read-only view of an inbound message that a [`Condition`] is evaluated against.
+pub struct MessageContext<'a> {
+    pub text: Option<&'a str>,
+    pub sender: &'a str,
+    pub from_me: bool,
+    pub channel: Channel,
+}
+
+impl<'a> MessageContext<'a> {
+    /// Extracts a `MessageContext` from an `IncomingMessage::NewMessage`, if that's what it is.
+    pub fn from_new_message(message: &'a IncomingMessage) -> Option<Self> {
+        match message {
+            IncomingMessage::NewMessage {
+                platform,
+                from_me,
+                sender,
+                message,
+                ..
+            } => Some(Self {
+                text: message.text.as_deref(),
+                sender,
+                from_me: *from_me,
+                channel: *platform,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// A predicate evaluated against an inbound message to pick a [`ConditionalBranch`].
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Condition {
+    TextMatches {
+        pattern: String,
+        #[serde(default)]
+        regex: bool,
+        /// Lazily compiled on first evaluation and reused for every later message.
+        #[serde(skip)]
+        compiled: OnceLock<Result<regex::Regex, regex::Error>>,
+    },
+    TimeBetween {
+        #[serde(rename = "startTime")]
+        start: String,
+        #[serde(rename = "endTime")]
+        end: String,
+    },
+    SenderIn {
+        list: Vec<String>,
+    },
+    ChannelIs {
+        channel: Channel,
+    },
+    FromMe {
+        equals: bool,
+    },
+    /// Holds only if every nested condition holds.
+    All {
+        conditions: Vec<Condition>,
+    },
+    /// Holds if any nested condition holds.
+    Any {
+        conditions: Vec<Condition>,
+    },
+}
+
+impl Condition {
+    /// `now` is a `"HH:MM"` wall-clock time, comparable with `TimeBranch::start_time`/`end_time`.
+    pub fn evaluate(&self, ctx: &MessageContext, now: &str) -> bool {
+        match self {
+            Condition::TextMatches {
+                pattern,
+                regex,
+                compiled,
+            } => {
+                let Some(text) = ctx.text else {
+                    return false;
+                };
+                if *regex {
+                    match compiled.get_or_init(|| regex::Regex::new(pattern)) {
+                        Ok(re) => re.is_match(text),
+                        Err(_) => false,
+                    }
+                } else {
+                    text.contains(pattern.as_str())
+                }
+            }
+            Condition::TimeBetween { start, end } => start.as_str() <= now && now <= end.as_str(),
+            Condition::SenderIn { list } => list.iter().any(|sender| sender == ctx.sender),
+            Condition::ChannelIs { channel } => *channel == ctx.channel,
+            Condition::FromMe { equals } => ctx.from_me == *equals,
+            Condition::All { conditions } => conditions.iter().all(|c| c.evaluate(ctx, now)),
+            Condition::Any { conditions } => conditions.iter().any(|c| c.evaluate(ctx, now)),
+        }
+    }
+}
+
 /// Helper struct for outgoing messages (to queue `agentic:queue:outgoing`)
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OutgoingMessage {
@@ -38,25 +219,428 @@ pub struct OutgoingMessage {
     pub payload: OutgoingPayload,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize)]
 pub struct OutgoingPayload {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub text: Option<String>,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub image: Option<MediaPayload>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+}
 
+/// A single outgoing media item, modelled on the ActivityPub attachment's
+/// `name`/`mediaType`/`url` fields (no ActivityPub `type` field is carried;
+/// the kind of media comes from `media_type`) so one message can carry documents, video,
+/// location pins, or a multi-image album instead of just one image and one audio.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    #[serde(rename = "mediaType")]
+    pub media_type: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub audio: Option<MediaPayload>,
-
+    pub name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub caption: Option<String>,
+    pub url: String,
+    /// Send as a voice note (e.g. WhatsApp ptt) rather than a regular audio attachment.
+    /// This is a send-method flag, independent of `media_type`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ptt: bool,
+}
 
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub ptt: Option<bool>,
+impl<'de> Deserialize<'de> for OutgoingPayload {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Raw {
+            text: Option<String>,
+            #[serde(default)]
+            attachments: Vec<Attachment>,
+            // Legacy single image/audio fields, kept for backward compatibility.
+            image: Option<MediaPayload>,
+            audio: Option<MediaPayload>,
+            caption: Option<String>,
+            ptt: Option<bool>,
+        }
+
+        let raw = Raw::deserialize(deserializer)?;
+        let mut attachments = raw.attachments;
+        // The legacy caption belongs to whichever single legacy media item is present;
+        // if neither is, it falls through onto `text` below rather than being dropped.
+        let mut caption = raw.caption;
+        if let Some(image) = raw.image {
+            attachments.push(Attachment {
+                media_type: "image/*".to_string(),
+                name: None,
+                caption: caption.take(),
+                url: image.url,
+                ptt: false,
+            });
+        }
+        if let Some(audio) = raw.audio {
+            let ptt = raw.ptt.unwrap_or(false);
+            attachments.push(Attachment {
+                media_type: if ptt {
+                    "audio/ogg".to_string()
+                } else {
+                    "audio/*".to_string()
+                },
+                name: None,
+                caption: caption.take(),
+                url: audio.url,
+                ptt,
+            });
+        }
+
+        let text = match (raw.text, caption) {
+            (Some(text), Some(caption)) => Some(format!("{text}\n{caption}")),
+            (Some(text), None) => Some(text),
+            (None, Some(caption)) => Some(caption),
+            (None, None) => None,
+        };
+
+        Ok(OutgoingPayload { text, attachments })
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MediaPayload {
     pub url: String,
 }
+
+/// Raised when an `OutgoingPayload` carries something the target channel declared it can't render.
+#[derive(Debug)]
+pub struct UnsupportedByChannel(pub &'static str);
+
+impl std::fmt::Display for UnsupportedByChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "target channel does not support {}", self.0)
+    }
+}
+
+impl std::error::Error for UnsupportedByChannel {}
+
+impl OutgoingPayload {
+    /// Checks this payload against what the target channel declared it can render,
+    /// e.g. rejecting `ptt`/audio for a text-only web channel.
+    pub fn validate_for(&self, capabilities: ChannelCapabilities) -> Result<(), UnsupportedByChannel> {
+        if self.text.is_some() && !capabilities.text {
+            return Err(UnsupportedByChannel("text"));
+        }
+        for attachment in &self.attachments {
+            if attachment.ptt && !capabilities.ptt {
+                return Err(UnsupportedByChannel("ptt"));
+            }
+            if !capabilities.allows_media_type(&attachment.media_type) {
+                return Err(UnsupportedByChannel("attachment"));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(condition: Condition) -> Condition {
+        let json = serde_json::to_string(&condition).expect("serialize");
+        serde_json::from_str(&json).expect("deserialize")
+    }
+
+    #[test]
+    fn text_matches_round_trips() {
+        let condition = Condition::TextMatches {
+            pattern: "hello".to_string(),
+            regex: false,
+            compiled: OnceLock::new(),
+        };
+        assert!(matches!(
+            round_trip(condition),
+            Condition::TextMatches { pattern, regex, .. }
+                if pattern == "hello" && !regex
+        ));
+    }
+
+    #[test]
+    fn time_between_round_trips() {
+        let condition = Condition::TimeBetween {
+            start: "09:00".to_string(),
+            end: "17:00".to_string(),
+        };
+        assert!(matches!(
+            round_trip(condition),
+            Condition::TimeBetween { start, end }
+                if start == "09:00" && end == "17:00"
+        ));
+    }
+
+    #[test]
+    fn sender_in_round_trips() {
+        let condition = Condition::SenderIn {
+            list: vec!["alice".to_string(), "bob".to_string()],
+        };
+        assert!(matches!(
+            round_trip(condition),
+            Condition::SenderIn { list } if list == ["alice", "bob"]
+        ));
+    }
+
+    #[test]
+    fn channel_is_round_trips() {
+        let condition = Condition::ChannelIs {
+            channel: Channel::WhatsApp,
+        };
+        assert!(matches!(
+            round_trip(condition),
+            Condition::ChannelIs { channel } if channel == Channel::WhatsApp
+        ));
+    }
+
+    #[test]
+    fn all_round_trips() {
+        let condition = Condition::All {
+            conditions: vec![
+                Condition::SenderIn {
+                    list: vec!["alice".to_string()],
+                },
+                Condition::ChannelIs {
+                    channel: Channel::Telegram,
+                },
+            ],
+        };
+        match round_trip(condition) {
+            Condition::All { conditions } => assert_eq!(conditions.len(), 2),
+            other => panic!("expected All, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn any_round_trips() {
+        let condition = Condition::Any {
+            conditions: vec![Condition::ChannelIs {
+                channel: Channel::Voice,
+            }],
+        };
+        match round_trip(condition) {
+            Condition::Any { conditions } => assert_eq!(conditions.len(), 1),
+            other => panic!("expected Any, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn from_me_round_trips() {
+        let condition = Condition::FromMe { equals: true };
+        assert!(matches!(
+            round_trip(condition),
+            Condition::FromMe { equals: true }
+        ));
+    }
+
+    fn ctx<'a>(text: Option<&'a str>, sender: &'a str, from_me: bool, channel: Channel) -> MessageContext<'a> {
+        MessageContext {
+            text,
+            sender,
+            from_me,
+            channel,
+        }
+    }
+
+    #[test]
+    fn text_matches_evaluates_plain_and_regex() {
+        let plain = Condition::TextMatches {
+            pattern: "hello".to_string(),
+            regex: false,
+            compiled: OnceLock::new(),
+        };
+        assert!(plain.evaluate(&ctx(Some("oh hello there"), "alice", false, Channel::WhatsApp), ""));
+        assert!(!plain.evaluate(&ctx(Some("goodbye"), "alice", false, Channel::WhatsApp), ""));
+
+        let regex = Condition::TextMatches {
+            pattern: "^\\d+$".to_string(),
+            regex: true,
+            compiled: OnceLock::new(),
+        };
+        assert!(regex.evaluate(&ctx(Some("12345"), "alice", false, Channel::WhatsApp), ""));
+        assert!(!regex.evaluate(&ctx(Some("not a number"), "alice", false, Channel::WhatsApp), ""));
+    }
+
+    #[test]
+    fn time_between_evaluates_boundaries() {
+        let condition = Condition::TimeBetween {
+            start: "09:00".to_string(),
+            end: "17:00".to_string(),
+        };
+        let c = ctx(None, "alice", false, Channel::WhatsApp);
+        assert!(condition.evaluate(&c, "09:00"));
+        assert!(condition.evaluate(&c, "17:00"));
+        assert!(condition.evaluate(&c, "12:30"));
+        assert!(!condition.evaluate(&c, "08:59"));
+        assert!(!condition.evaluate(&c, "17:01"));
+    }
+
+    #[test]
+    fn sender_in_evaluates_membership() {
+        let condition = Condition::SenderIn {
+            list: vec!["alice".to_string(), "bob".to_string()],
+        };
+        assert!(condition.evaluate(&ctx(None, "alice", false, Channel::WhatsApp), ""));
+        assert!(!condition.evaluate(&ctx(None, "carol", false, Channel::WhatsApp), ""));
+    }
+
+    #[test]
+    fn from_me_evaluates_against_context() {
+        let condition = Condition::FromMe { equals: true };
+        assert!(condition.evaluate(&ctx(None, "alice", true, Channel::WhatsApp), ""));
+        assert!(!condition.evaluate(&ctx(None, "alice", false, Channel::WhatsApp), ""));
+    }
+
+    #[test]
+    fn all_and_any_compose_nested_conditions() {
+        let all = Condition::All {
+            conditions: vec![
+                Condition::FromMe { equals: false },
+                Condition::ChannelIs {
+                    channel: Channel::WhatsApp,
+                },
+            ],
+        };
+        assert!(all.evaluate(&ctx(None, "alice", false, Channel::WhatsApp), ""));
+        assert!(!all.evaluate(&ctx(None, "alice", true, Channel::WhatsApp), ""));
+
+        let any = Condition::Any {
+            conditions: vec![
+                Condition::SenderIn {
+                    list: vec!["bob".to_string()],
+                },
+                Condition::ChannelIs {
+                    channel: Channel::Telegram,
+                },
+            ],
+        };
+        assert!(any.evaluate(&ctx(None, "alice", false, Channel::Telegram), ""));
+        assert!(!any.evaluate(&ctx(None, "alice", false, Channel::WhatsApp), ""));
+    }
+
+    fn branch_content(r#type: &str) -> BranchContent {
+        BranchContent {
+            r#type: r#type.to_string(),
+            content: None,
+            media_url: None,
+        }
+    }
+
+    #[test]
+    fn conditional_metadata_picks_first_matching_branch_then_falls_back() {
+        let metadata = ConditionalMetadata {
+            branches: vec![
+                ConditionalBranch {
+                    when: Condition::SenderIn {
+                        list: vec!["alice".to_string()],
+                    },
+                    content: branch_content("alice-branch"),
+                },
+                ConditionalBranch {
+                    when: Condition::ChannelIs {
+                        channel: Channel::WhatsApp,
+                    },
+                    content: branch_content("whatsapp-branch"),
+                },
+            ],
+            fallback: branch_content("fallback"),
+        };
+
+        let alice = ctx(None, "alice", false, Channel::Telegram);
+        assert_eq!(metadata.evaluate(&alice, "").r#type, "alice-branch");
+
+        let bob_on_whatsapp = ctx(None, "bob", false, Channel::WhatsApp);
+        assert_eq!(metadata.evaluate(&bob_on_whatsapp, "").r#type, "whatsapp-branch");
+
+        let carol_on_telegram = ctx(None, "carol", false, Channel::Telegram);
+        assert_eq!(metadata.evaluate(&carol_on_telegram, "").r#type, "fallback");
+    }
+
+    #[test]
+    fn conditional_metadata_deserializes_legacy_time_branch_json() {
+        let json = r#"{
+            "branches": [
+                {"startTime": "09:00", "endTime": "17:00", "type": "text", "content": "business hours"}
+            ],
+            "fallback": {"type": "text", "content": "after hours"}
+        }"#;
+
+        let metadata: ConditionalMetadata = serde_json::from_str(json).expect("deserialize legacy shape");
+        assert_eq!(metadata.branches.len(), 1);
+        assert!(matches!(
+            metadata.branches[0].when,
+            Condition::TimeBetween { ref start, ref end }
+                if start == "09:00" && end == "17:00"
+        ));
+        assert_eq!(metadata.branches[0].content.content.as_deref(), Some("business hours"));
+        assert_eq!(metadata.fallback.content.as_deref(), Some("after hours"));
+
+        let ctx = ctx(None, "alice", false, Channel::WhatsApp);
+        assert_eq!(metadata.evaluate(&ctx, "12:00").content.as_deref(), Some("business hours"));
+        assert_eq!(metadata.evaluate(&ctx, "20:00").content.as_deref(), Some("after hours"));
+    }
+
+    #[test]
+    fn legacy_image_with_caption_lands_caption_on_attachment() {
+        let json = r#"{"image": {"url": "https://example.com/pic.png"}, "caption": "look at this"}"#;
+        let payload: OutgoingPayload = serde_json::from_str(json).expect("deserialize legacy image");
+
+        assert_eq!(payload.text, None);
+        assert_eq!(payload.attachments.len(), 1);
+        let attachment = &payload.attachments[0];
+        assert_eq!(attachment.media_type, "image/*");
+        assert_eq!(attachment.caption.as_deref(), Some("look at this"));
+        assert_eq!(attachment.url, "https://example.com/pic.png");
+        assert!(!attachment.ptt);
+    }
+
+    #[test]
+    fn legacy_audio_with_ptt_becomes_voice_note_attachment() {
+        let json = r#"{"audio": {"url": "https://example.com/note.oga"}, "ptt": true}"#;
+        let payload: OutgoingPayload = serde_json::from_str(json).expect("deserialize legacy audio");
+
+        assert_eq!(payload.attachments.len(), 1);
+        let attachment = &payload.attachments[0];
+        assert_eq!(attachment.media_type, "audio/ogg");
+        assert!(attachment.ptt);
+        assert_eq!(attachment.caption, None);
+    }
+
+    #[test]
+    fn legacy_audio_without_ptt_is_regular_audio_attachment() {
+        let json = r#"{"audio": {"url": "https://example.com/note.mp3"}}"#;
+        let payload: OutgoingPayload = serde_json::from_str(json).expect("deserialize legacy audio");
+
+        let attachment = &payload.attachments[0];
+        assert_eq!(attachment.media_type, "audio/*");
+        assert!(!attachment.ptt);
+    }
+
+    #[test]
+    fn text_and_caption_merge_when_no_legacy_media_present() {
+        let json = r#"{"text": "hello", "caption": "world"}"#;
+        let payload: OutgoingPayload = serde_json::from_str(json).expect("deserialize text+caption");
+
+        assert_eq!(payload.text.as_deref(), Some("hello\nworld"));
+        assert!(payload.attachments.is_empty());
+    }
+
+    #[test]
+    fn current_attachments_shape_round_trips_unchanged() {
+        let json = r#"{"text": "hi", "attachments": [{"mediaType": "video/mp4", "url": "https://example.com/v.mp4"}]}"#;
+        let payload: OutgoingPayload = serde_json::from_str(json).expect("deserialize current shape");
+
+        assert_eq!(payload.text.as_deref(), Some("hi"));
+        assert_eq!(payload.attachments.len(), 1);
+        assert_eq!(payload.attachments[0].media_type, "video/mp4");
+
+        let original: serde_json::Value = serde_json::from_str(json).expect("parse original");
+        let reserialized = serde_json::to_value(&payload).expect("serialize current shape");
+        assert_eq!(reserialized, original);
+    }
+}
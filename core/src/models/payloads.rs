@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::channels::Channel;
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum IncomingMessage {
@@ -9,7 +11,7 @@ pub enum IncomingMessage {
         bot_id: String,
         session_id: String,
         identifier: String,
-        platform: String,
+        platform: Channel,
         from_me: bool,
         sender: String,
         message: MessageContent,
@@ -26,6 +28,55 @@ pub enum IncomingMessage {
         execution_id: String,
         step_order: i32,
     },
+    /// Opens the Gateway link for a bot, identifying the connecting client and protocol version
+    #[serde(rename = "CONNECT")]
+    Connect {
+        bot_id: String,
+        client: String,
+        protocol_version: String,
+    },
+    /// Periodic heartbeat on an open Gateway link
+    #[serde(rename = "PING")]
+    Ping { bot_id: String, sent_at: i64 },
+    /// Gracefully tears down a Gateway link
+    #[serde(rename = "CLOSE")]
+    Close {
+        bot_id: String,
+        reason: Option<String>,
+    },
+}
+
+/// Sent back to the Gateway on the outgoing link in response to a `PING`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum OutgoingControlMessage {
+    #[serde(rename = "PONG")]
+    Pong { bot_id: String, acked_at: i64 },
+}
+
+/// Governs when a `bot_id` is considered offline because its heartbeat lapsed.
+///
+/// Once `last_ping_at` is older than `heartbeat_timeout_secs`, the worker should
+/// mark the bot offline and stop draining its outgoing queue until the next `PING`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LivenessConfig {
+    pub heartbeat_timeout_secs: u64,
+}
+
+impl Default for LivenessConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_timeout_secs: 60,
+        }
+    }
+}
+
+impl LivenessConfig {
+    /// Whether a bot whose last heartbeat was seen at `last_ping_at` is still alive at `now`
+    /// (both expressed in the same epoch-seconds unit as `MessageContent::timestamp`).
+    pub fn is_alive(&self, last_ping_at: i64, now: i64) -> bool {
+        now.saturating_sub(last_ping_at) <= self.heartbeat_timeout_secs as i64
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]